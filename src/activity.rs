@@ -0,0 +1,45 @@
+//! Tracks whether the Link Plugin has received a tick recently.
+//!
+//! Mumble's Link plugin only treats an application as linked while ticks
+//! keep arriving (`last_tick_time`); this mirrors that by recording a
+//! monotonic timestamp on every update and reporting staleness once it's
+//! been quiet for longer than the configured timeout.
+
+use std::time::{Duration, Instant};
+
+/// Default timeout before a link with no new updates is considered stale.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time of the most recent successful update, with a configurable timeout.
+pub struct LastTick {
+    at: Option<Instant>,
+    timeout: Duration,
+}
+
+impl LastTick {
+    pub const fn new() -> Self {
+        LastTick {
+            at: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Change how long to wait before a quiet link is considered stale.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Record that a tick just happened.
+    pub fn record(&mut self) {
+        self.at = Some(Instant::now());
+    }
+
+    /// Whether at least the configured timeout has elapsed since the last
+    /// recorded tick. A link that has never ticked is considered stale.
+    pub fn is_stale(&self) -> bool {
+        match self.at {
+            Some(at) => at.elapsed() >= self.timeout,
+            None => true,
+        }
+    }
+}
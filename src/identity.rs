@@ -0,0 +1,45 @@
+//! Structured context/identity payloads written into the Link mapping.
+//!
+//! Mumble only places two applications in the same positional-audio bubble
+//! when their `context` bytes match exactly, and displays `identity` as a
+//! JSON blob that linked games populate themselves. This builds both from
+//! structured fields instead of a single opaque string, so e.g. server and
+//! dimension actually gate who shares audio.
+
+use serde::Serialize;
+
+/// Identifies which positional-audio bubble a player belongs to: two
+/// players only share audio when this serializes identically.
+///
+/// `prefix` is included as a field (rather than prepended to the
+/// serialized JSON) so different linked applications never collide on an
+/// identical context even when their other fields match, while `context`
+/// stays valid JSON.
+#[derive(Serialize)]
+pub struct Context<'a> {
+    pub prefix: &'a str,
+    pub server: &'a str,
+    pub port: i32,
+    pub dimension: &'a str,
+}
+
+impl<'a> Context<'a> {
+    /// Serialize to the canonical JSON written into `MumbleLink::context`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Context is always serializable")
+    }
+}
+
+/// Per-player metadata Mumble can show alongside the linked application.
+#[derive(Serialize)]
+pub struct Identity<'a> {
+    pub name: &'a str,
+    pub uuid: &'a str,
+    pub team: &'a str,
+}
+
+impl<'a> Identity<'a> {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Identity is always serializable")
+    }
+}
@@ -1,15 +1,9 @@
 #![allow(non_snake_case)]
 
 use std::mem;
-use std::ptr::{self, copy_nonoverlapping as memcpy};
+use std::ptr::copy_nonoverlapping as memcpy;
 use std::sync::{Arc, Mutex};
 
-use nix::errno::Errno;
-use nix::fcntl::OFlag;
-use nix::sys::mman::{self, MapFlags, ProtFlags};
-use nix::sys::stat::Mode;
-use nix::unistd;
-
 use jni::errors::Error as JNIError;
 use jni::objects::{JObject, JString};
 use jni::strings::JNIString;
@@ -20,18 +14,37 @@ use lazy_static::lazy_static;
 
 use widestring::WideCString;
 
+mod activity;
+mod identity;
+mod platform;
+mod snapshot;
+mod transform;
+
 lazy_static! {
-    static ref MUMBLE_LINK: Arc<Mutex<Option<&'static mut MumbleLink>>> =
+    static ref MUMBLE_LINK: Arc<Mutex<Option<platform::MumbleLinkHandle>>> =
         Arc::new(Mutex::new(None));
-    static ref PLUGIN_NAME: WideCString = WideCString::from_str("Minecraft").unwrap();
-    static ref PLUGIN_DESCRIPTION: WideCString =
-        WideCString::from_str("Mumble Link implementation for Lunar Client.").unwrap();
+    static ref LAST_TICK: Mutex<activity::LastTick> = Mutex::new(activity::LastTick::new());
+    static ref PLUGIN_NAME: Mutex<WideCString> =
+        Mutex::new(WideCString::from_str("Minecraft").unwrap());
+    static ref PLUGIN_DESCRIPTION: Mutex<WideCString> =
+        Mutex::new(WideCString::from_str("Mumble Link implementation for Lunar Client.").unwrap());
+    /// Prefix added to the context before it's written into the mapping, so
+    /// different linked applications don't collide on an identical context.
+    static ref CONTEXT_PREFIX: Mutex<String> = Mutex::new(String::new());
 }
 
 // JVM types for JNI
 const MUMBLE_VEC_TYPE: &'static str = "Lcom/moonsworth/client/mumble/MumbleVec;";
 const JSTRING_TYPE: &'static str = "Ljava/lang/String;";
 
+/// The platform's native wide-char width: `wchar_t` is 16 bits on Windows
+/// and 32 bits everywhere else, and `widestring::WideCString` follows the
+/// same split, so the mapping's wide-char fields have to match.
+#[cfg(windows)]
+type WideChar = u16;
+#[cfg(not(windows))]
+type WideChar = u32;
+
 /// A struct representation of the shared memory of the Link Plugin.
 #[repr(C)]
 struct MumbleLink {
@@ -42,68 +55,53 @@ struct MumbleLink {
     avatar_front: [f32; 3],
     avatar_top: [f32; 3],
 
-    name: [u32; 256],
+    name: [WideChar; 256],
 
     camera_position: [f32; 3],
     camera_front: [f32; 3],
     camera_top: [f32; 3],
 
-    identity: [u32; 256],
+    identity: [WideChar; 256],
 
     context_len: u32,
     context: [u8; 256],
 
-    description: [u32; 2048],
+    description: [WideChar; 2048],
 }
 
 const MUMBLE_LINK_SIZE: usize = mem::size_of::<MumbleLink>();
 
-/// Open the shared memory for the Mumble Link Plugin
-fn init_mumble_link() -> Result<&'static mut MumbleLink, nix::Error> {
-    unsafe {
-        let uid = unistd::getuid();
-        let shm_name = format!("/MumbleLink.{}", uid);
-
-        let raw_fd = mman::shm_open(
-            shm_name.as_str(),
-            OFlag::O_RDWR,
-            Mode::S_IRUSR | Mode::S_IWUSR,
-        )?;
-
-        unistd::ftruncate(raw_fd, MUMBLE_LINK_SIZE as i64)?;
-
-        let ptr = mman::mmap(
-            ptr::null_mut(),
-            MUMBLE_LINK_SIZE,
-            ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
-            MapFlags::MAP_SHARED,
-            raw_fd,
-            0,
-        )?;
-
-        unistd::close(raw_fd)?;
-
-        Ok(mem::transmute(ptr))
-    }
-}
-
-/// Convert a Mumble Vec Java object to a float slice.
-/// The resultant slice will be in X Z Y from due to a bug in the original DLL.
-fn mumble_vec_to_float_slice(env: JNIEnv, obj: JObject) -> Result<[f32; 3], JNIError> {
-    // XXX: Someone messed up the conversion from a MumbleVec object to a float[3] array in the
-    // Windows DLL. They did X Z Y instead of X Y Z. I have wasted a huge amount of time on that.
-    // I have already reported the issue in the lunar client Discord.
-    // If anyone from the lunar client dev team are reading this. Please fix that.
+/// Read the raw x/y/z fields off a Mumble Vec Java object, in Minecraft's
+/// own coordinate order.
+fn read_vec_fields(env: JNIEnv, obj: JObject) -> Result<[f32; 3], JNIError> {
     Ok([
         env.get_field(obj, "xCoord", "D")?.d()? as f32,
-        env.get_field(obj, "zCoord", "D")?.d()? as f32,
         env.get_field(obj, "yCoord", "D")?.d()? as f32,
+        env.get_field(obj, "zCoord", "D")?.d()? as f32,
     ])
 }
 
-/// Get a float slice from a Java object
-fn get_vec(env: JNIEnv, link_data: JObject, name: &str) -> Result<[f32; 3], JNIError> {
-    mumble_vec_to_float_slice(env, env.get_field(link_data, name, MUMBLE_VEC_TYPE)?.l()?)
+/// Get a position vector from a Java object, mapped into Mumble's axes.
+fn get_position(env: JNIEnv, link_data: JObject, name: &str) -> Result<[f32; 3], JNIError> {
+    let obj = env.get_field(link_data, name, MUMBLE_VEC_TYPE)?.l()?;
+    let [x, y, z] = read_vec_fields(env, obj)?;
+    Ok(transform::minecraft_to_mumble(x, y, z))
+}
+
+/// Get a unit-length direction vector (front/top) from a Java object,
+/// falling back to `default` if the source vector is (near) zero.
+fn get_direction(
+    env: JNIEnv,
+    link_data: JObject,
+    name: &str,
+    default: [f32; 3],
+) -> Result<[f32; 3], JNIError> {
+    let obj = env.get_field(link_data, name, MUMBLE_VEC_TYPE)?.l()?;
+    let [x, y, z] = read_vec_fields(env, obj)?;
+    Ok(transform::normalize(
+        transform::minecraft_to_mumble(x, y, z),
+        default,
+    ))
 }
 
 /// Get a JNIString from a Java object
@@ -112,9 +110,24 @@ fn get_jstring(env: JNIEnv, obj: JObject, name: &str) -> Result<JNIString, JNIEr
     Ok(env.get_string(jstring)?.to_owned())
 }
 
-/// Get a WideCString from a Java object
-fn get_widestring(env: JNIEnv, obj: JObject, name: &str) -> Result<WideCString, JNIError> {
-    Ok(WideCString::from_str(get_jstring(env, obj, name)?.to_string_lossy()).unwrap())
+/// Get an i32 from a Java object
+fn get_jint(env: JNIEnv, obj: JObject, name: &str) -> Result<i32, JNIError> {
+    env.get_field(obj, name, "I")?.i()
+}
+
+/// Copy a nul-terminated wide string into a fixed-size buffer, truncating
+/// (and re-terminating) if it doesn't fit so callers can't be made to
+/// overflow into the mapping's neighboring fields.
+fn write_wide(dst: &mut [WideChar], src: &WideCString) {
+    let capacity = dst.len();
+    let mut chars = src.as_slice_with_nul().to_vec();
+    if chars.len() > capacity {
+        chars.truncate(capacity - 1);
+        chars.push(0);
+    }
+    unsafe {
+        memcpy(chars.as_ptr(), dst.as_mut_ptr(), chars.len());
+    }
 }
 
 /// Update the mumble_link with the link_data object
@@ -125,78 +138,165 @@ fn update_mumblelink(
 ) -> Result<(), JNIError> {
     unsafe {
         if mumble_link.ui_version != 2 {
-            let name = PLUGIN_NAME.as_slice_with_nul();
-            let description = PLUGIN_DESCRIPTION.as_slice_with_nul();
-            memcpy(name.as_ptr(), mumble_link.name.as_mut_ptr(), name.len());
-            memcpy(
-                description.as_ptr(),
-                mumble_link.description.as_mut_ptr(),
-                description.len(),
-            );
+            let plugin_name = PLUGIN_NAME.lock().expect("Failed to lock Mutex");
+            let plugin_description = PLUGIN_DESCRIPTION.lock().expect("Failed to lock Mutex");
+            write_wide(&mut mumble_link.name, &plugin_name);
+            write_wide(&mut mumble_link.description, &plugin_description);
 
             mumble_link.ui_version = 2;
         }
 
         mumble_link.ui_tick += 1;
 
-        mumble_link
-            .avatar_position
-            .copy_from_slice(&get_vec(env, link_data, "avatarPosition")?);
-        mumble_link
-            .avatar_front
-            .copy_from_slice(&get_vec(env, link_data, "avatarFront")?);
+        const DEFAULT_FRONT: [f32; 3] = [0.0, 0.0, 1.0];
+        const DEFAULT_TOP: [f32; 3] = [0.0, 1.0, 0.0];
+
+        mumble_link.avatar_position.copy_from_slice(&get_position(
+            env,
+            link_data,
+            "avatarPosition",
+        )?);
+        let avatar_front = get_direction(env, link_data, "avatarFront", DEFAULT_FRONT)?;
+        let avatar_top = get_direction(env, link_data, "avatarTop", DEFAULT_TOP)?;
+        mumble_link.avatar_front.copy_from_slice(&avatar_front);
         mumble_link
             .avatar_top
-            .copy_from_slice(&get_vec(env, link_data, "avatarTop")?);
-
-        mumble_link
-            .camera_position
-            .copy_from_slice(&get_vec(env, link_data, "cameraPosition")?);
-        mumble_link
-            .camera_front
-            .copy_from_slice(&get_vec(env, link_data, "cameraFront")?);
+            .copy_from_slice(&transform::orthogonalize_top(
+                avatar_front,
+                avatar_top,
+                DEFAULT_TOP,
+            ));
+
+        mumble_link.camera_position.copy_from_slice(&get_position(
+            env,
+            link_data,
+            "cameraPosition",
+        )?);
+        let camera_front = get_direction(env, link_data, "cameraFront", DEFAULT_FRONT)?;
+        let camera_top = get_direction(env, link_data, "cameraTop", DEFAULT_TOP)?;
+        mumble_link.camera_front.copy_from_slice(&camera_front);
         mumble_link
             .camera_top
-            .copy_from_slice(&get_vec(env, link_data, "cameraTop")?);
-
-        let player_name = get_widestring(env, link_data, "playerName")?;
-        let player_bytes = player_name.as_slice_with_nul();
-        memcpy(
-            player_bytes.as_ptr(),
-            mumble_link.identity.as_mut_ptr(),
-            player_bytes.len(),
-        );
-
-        let context = get_jstring(env, link_data, "context")?;
+            .copy_from_slice(&transform::orthogonalize_top(
+                camera_front,
+                camera_top,
+                DEFAULT_TOP,
+            ));
+
+        let player_name = get_jstring(env, link_data, "playerName")?
+            .to_string_lossy()
+            .to_string();
+        let player_uuid = get_jstring(env, link_data, "playerUuid")?
+            .to_string_lossy()
+            .to_string();
+        let team = get_jstring(env, link_data, "team")?
+            .to_string_lossy()
+            .to_string();
+
+        let identity_json = identity::Identity {
+            name: &player_name,
+            uuid: &player_uuid,
+            team: &team,
+        }
+        .to_json();
+        let identity_wide = WideCString::from_str(identity_json).unwrap();
+        write_wide(&mut mumble_link.identity, &identity_wide);
+
+        let server_host = get_jstring(env, link_data, "serverHost")?
+            .to_string_lossy()
+            .to_string();
+        let server_port = get_jint(env, link_data, "serverPort")?;
+        let dimension = get_jstring(env, link_data, "dimension")?
+            .to_string_lossy()
+            .to_string();
+
+        let context_prefix = CONTEXT_PREFIX.lock().expect("Failed to lock Mutex").clone();
+        let context_json = identity::Context {
+            prefix: &context_prefix,
+            server: &server_host,
+            port: server_port,
+            dimension: &dimension,
+        }
+        .to_json();
         // Seems that context doesn't rely on a nul terminator
-        let context_bytes = context.to_bytes();
-        let context_len = context_bytes.len();
+        let context_bytes = context_json.as_bytes();
+        let max_len = mumble_link.context.len();
+        let context_len = if context_bytes.len() > max_len {
+            // Truncate at a UTF-8 char boundary so a long server host or
+            // dimension name doesn't overflow into the adjacent fields.
+            let mut len = max_len;
+            while !context_json.is_char_boundary(len) {
+                len -= 1;
+            }
+            len
+        } else {
+            context_bytes.len()
+        };
         memcpy(
             context_bytes.as_ptr(),
             mumble_link.context.as_mut_ptr(),
-            context_bytes.len(),
+            context_len,
         );
         mumble_link.context_len = context_len as u32;
     }
     Ok(())
 }
 
+/// Set the plugin name, description, and context prefix that get written
+/// into the mapping, so different linked applications can identify
+/// themselves distinctly instead of all appearing as "Minecraft".
+#[no_mangle]
+pub extern "system" fn Java_com_moonsworth_client_mumble_MumbleLink_configure(
+    env: JNIEnv<'static>,
+    _this: JObject,
+    name: JString,
+    description: JString,
+    context_prefix: JString,
+) {
+    let name = env
+        .get_string(name)
+        .expect("invalid name")
+        .to_string_lossy()
+        .to_string();
+    let description = env
+        .get_string(description)
+        .expect("invalid description")
+        .to_string_lossy()
+        .to_string();
+    let context_prefix = env
+        .get_string(context_prefix)
+        .expect("invalid context prefix")
+        .to_string_lossy()
+        .to_string();
+
+    *PLUGIN_NAME.lock().expect("Failed to lock Mutex") = WideCString::from_str(name).unwrap();
+    *PLUGIN_DESCRIPTION.lock().expect("Failed to lock Mutex") =
+        WideCString::from_str(description).unwrap();
+    *CONTEXT_PREFIX.lock().expect("Failed to lock Mutex") = context_prefix;
+
+    // Force the name/description to be rewritten into the mapping on the next update.
+    let arc = MUMBLE_LINK.clone();
+    let mut lock = arc.lock().expect("Failed to lock Mutex");
+    if let Some(handle) = lock.as_mut() {
+        handle.as_mut().ui_version = 0;
+    }
+}
+
 #[no_mangle]
 pub extern "system" fn Java_com_moonsworth_client_mumble_MumbleLink_init(
     _env: JNIEnv<'static>,
     _this: JObject,
 ) -> jint {
-    match init_mumble_link() {
-        Ok(link) => {
+    match platform::open_mumble_link() {
+        Ok(handle) => {
             let arc = MUMBLE_LINK.clone();
             let mut mumble_link = arc.lock().unwrap();
-            *mumble_link = Some(link);
+            *mumble_link = Some(handle);
             0
         }
         Err(e) => {
-            let errno = e.as_errno().unwrap_or(Errno::UnknownErrno);
-            if errno != Errno::ENOENT {
-                eprintln!("Errno: {}", errno);
+            if !e.is_not_found() {
+                eprintln!("MumbleLink error: {}", e);
             }
             -1
         }
@@ -211,9 +311,79 @@ pub extern "system" fn Java_com_moonsworth_client_mumble_MumbleLink_update(
 ) {
     let arc = MUMBLE_LINK.clone();
     let mut lock = arc.lock().expect("Failed to lock Mutex");
-    let mumble_link = lock.as_mut().expect("MumbleLink is None");
+    let handle = lock.as_mut().expect("MumbleLink is None");
+    let mumble_link = handle.as_mut();
 
     if let Err(e) = update_mumblelink(env, link_data, mumble_link) {
         panic!("JNIError: {}", e);
     }
+
+    LAST_TICK.lock().expect("Failed to lock Mutex").record();
+}
+
+/// Poll for link inactivity and, if the timeout has elapsed since the last
+/// real update, mark the data invalid (`ui_version = 0`) so Mumble falls
+/// back to non-positional audio instead of using stale coordinates.
+#[no_mangle]
+pub extern "system" fn Java_com_moonsworth_client_mumble_MumbleLink_deactivate(
+    _env: JNIEnv<'static>,
+    _this: JObject,
+) {
+    if !LAST_TICK.lock().expect("Failed to lock Mutex").is_stale() {
+        return;
+    }
+
+    let arc = MUMBLE_LINK.clone();
+    let mut lock = arc.lock().expect("Failed to lock Mutex");
+    if let Some(handle) = lock.as_mut() {
+        handle.as_mut().ui_version = 0;
+    }
+}
+
+/// Change how long to wait, in milliseconds, before a quiet link is
+/// considered stale by `deactivate`.
+#[no_mangle]
+pub extern "system" fn Java_com_moonsworth_client_mumble_MumbleLink_setTimeoutMillis(
+    _env: JNIEnv<'static>,
+    _this: JObject,
+    timeout_ms: jint,
+) {
+    LAST_TICK
+        .lock()
+        .expect("Failed to lock Mutex")
+        .set_timeout(std::time::Duration::from_millis(timeout_ms.max(0) as u64));
+}
+
+/// Unmap the shared memory and reset the global handle. Safe to call when
+/// already closed (or never opened), mirroring Mumble's own `mumble_shutdown`.
+#[no_mangle]
+pub extern "system" fn Java_com_moonsworth_client_mumble_MumbleLink_close(
+    _env: JNIEnv<'static>,
+    _this: JObject,
+) {
+    let arc = MUMBLE_LINK.clone();
+    let mut lock = arc.lock().expect("Failed to lock Mutex");
+    if let Some(handle) = lock.take() {
+        handle.close();
+    }
+}
+
+/// Read the current state of the link mapping as JSON, for debugging and
+/// for overlays or companion processes that want to observe positional
+/// state without mapping the shared memory themselves.
+#[no_mangle]
+pub extern "system" fn Java_com_moonsworth_client_mumble_MumbleLink_snapshot(
+    env: JNIEnv<'static>,
+    _this: JObject,
+) -> jni::sys::jstring {
+    let arc = MUMBLE_LINK.clone();
+    let mut lock = arc.lock().expect("Failed to lock Mutex");
+    let json = match lock.as_mut() {
+        Some(handle) => snapshot::Snapshot::read(handle.as_mut()).to_json(),
+        None => "null".to_string(),
+    };
+
+    env.new_string(json)
+        .expect("Failed to create Java string")
+        .into_inner()
 }
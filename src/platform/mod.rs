@@ -0,0 +1,51 @@
+//! Platform-specific shared-memory backends for the Link Plugin.
+//!
+//! Mumble's own Link plugin exposes the same `LinkedMem` layout over POSIX
+//! `shm_open`/`mmap` on Linux/macOS and a named `CreateFileMappingW` on
+//! Windows. [`MumbleLinkHandle`] hides that behind one type so the JNI layer
+//! never has to branch on target OS.
+
+use std::fmt;
+
+#[cfg(unix)]
+mod posix;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(unix)]
+pub use posix::{open_mumble_link, MumbleLinkHandle};
+#[cfg(windows)]
+pub use windows::{open_mumble_link, MumbleLinkHandle};
+
+/// An error from opening the platform shared-memory mapping.
+#[derive(Debug)]
+pub enum LinkError {
+    #[cfg(unix)]
+    Nix(nix::Error),
+    #[cfg(windows)]
+    Os(u32),
+}
+
+impl LinkError {
+    /// Whether this error just means Mumble hasn't created the link file yet,
+    /// as opposed to a real failure worth logging.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            #[cfg(unix)]
+            LinkError::Nix(e) => e.as_errno() == Some(nix::errno::Errno::ENOENT),
+            #[cfg(windows)]
+            LinkError::Os(code) => *code == winapi::shared::winerror::ERROR_FILE_NOT_FOUND,
+        }
+    }
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(unix)]
+            LinkError::Nix(e) => write!(f, "{}", e),
+            #[cfg(windows)]
+            LinkError::Os(code) => write!(f, "OS error {}", code),
+        }
+    }
+}
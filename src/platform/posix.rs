@@ -0,0 +1,68 @@
+use std::mem;
+use std::ptr;
+
+use nix::fcntl::OFlag;
+use nix::sys::mman::{self, MapFlags, ProtFlags};
+use nix::sys::stat::Mode;
+use nix::unistd;
+
+use crate::{MumbleLink, MUMBLE_LINK_SIZE};
+
+use super::LinkError;
+
+/// A handle to the POSIX `shm_open`/`mmap` mapping backing the Link Plugin.
+pub struct MumbleLinkHandle {
+    ptr: *mut MumbleLink,
+}
+
+// SAFETY: the raw pointer is only ever touched through `MUMBLE_LINK`'s
+// mutex, which already guarantees exclusive access from one thread at a
+// time, so there is never a data race from sending it across threads.
+unsafe impl Send for MumbleLinkHandle {}
+
+impl MumbleLinkHandle {
+    /// Borrow the mapped `MumbleLink` for as long as the handle is open.
+    pub fn as_mut(&mut self) -> &'static mut MumbleLink {
+        unsafe { mem::transmute(self.ptr) }
+    }
+
+    /// Unmap the shared memory, consuming the handle.
+    pub fn close(self) {
+        unsafe {
+            let _ = mman::munmap(self.ptr as *mut _, MUMBLE_LINK_SIZE);
+        }
+    }
+}
+
+/// Open the shared memory for the Link Plugin (`/MumbleLink.<uid>`).
+pub fn open_mumble_link() -> Result<MumbleLinkHandle, LinkError> {
+    unsafe {
+        let uid = unistd::getuid();
+        let shm_name = format!("/MumbleLink.{}", uid);
+
+        let raw_fd = mman::shm_open(
+            shm_name.as_str(),
+            OFlag::O_RDWR,
+            Mode::S_IRUSR | Mode::S_IWUSR,
+        )
+        .map_err(LinkError::Nix)?;
+
+        unistd::ftruncate(raw_fd, MUMBLE_LINK_SIZE as i64).map_err(LinkError::Nix)?;
+
+        let ptr = mman::mmap(
+            ptr::null_mut(),
+            MUMBLE_LINK_SIZE,
+            ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+            MapFlags::MAP_SHARED,
+            raw_fd,
+            0,
+        )
+        .map_err(LinkError::Nix)?;
+
+        unistd::close(raw_fd).map_err(LinkError::Nix)?;
+
+        Ok(MumbleLinkHandle {
+            ptr: ptr as *mut MumbleLink,
+        })
+    }
+}
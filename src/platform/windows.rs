@@ -0,0 +1,70 @@
+use std::mem;
+
+use widestring::WideCString;
+use winapi::shared::minwindef::FALSE;
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::memoryapi::{
+    MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, FILE_MAP_ALL_ACCESS,
+};
+use winapi::um::winnt::HANDLE;
+
+use crate::{MumbleLink, MUMBLE_LINK_SIZE};
+
+use super::LinkError;
+
+/// A handle to the Windows file-mapping view backing the Link Plugin.
+pub struct MumbleLinkHandle {
+    mapping: HANDLE,
+    ptr: *mut MumbleLink,
+}
+
+// SAFETY: the raw handle and pointer are only ever touched through
+// `MUMBLE_LINK`'s mutex, which already guarantees exclusive access from
+// one thread at a time, so there is never a data race from sending them
+// across threads.
+unsafe impl Send for MumbleLinkHandle {}
+
+impl MumbleLinkHandle {
+    /// Borrow the mapped `MumbleLink` for as long as the handle is open.
+    pub fn as_mut(&mut self) -> &'static mut MumbleLink {
+        unsafe { mem::transmute(self.ptr) }
+    }
+
+    /// Unmap the view and close the mapping handle, consuming the handle.
+    pub fn close(self) {
+        unsafe {
+            UnmapViewOfFile(self.ptr as _);
+            CloseHandle(self.mapping);
+        }
+    }
+}
+
+/// Open the named file mapping for the Link Plugin (`MumbleLink`). Like the
+/// POSIX path, this only opens a mapping Mumble has already created; it
+/// never creates one itself, so a missing Mumble surfaces as `ERROR_FILE_NOT_FOUND`
+/// (see `LinkError::is_not_found`) instead of silently succeeding.
+pub fn open_mumble_link() -> Result<MumbleLinkHandle, LinkError> {
+    unsafe {
+        let name = WideCString::from_str("MumbleLink").unwrap();
+
+        let mapping = OpenFileMappingW(FILE_MAP_ALL_ACCESS, FALSE, name.as_ptr());
+
+        if mapping.is_null() {
+            return Err(LinkError::Os(GetLastError()));
+        }
+
+        let view = MapViewOfFile(mapping, FILE_MAP_ALL_ACCESS, 0, 0, MUMBLE_LINK_SIZE);
+
+        if view.is_null() {
+            let err = GetLastError();
+            CloseHandle(mapping);
+            return Err(LinkError::Os(err));
+        }
+
+        Ok(MumbleLinkHandle {
+            mapping,
+            ptr: view as *mut MumbleLink,
+        })
+    }
+}
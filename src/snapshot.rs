@@ -0,0 +1,66 @@
+//! A read-side view of the Link mapping.
+//!
+//! The crate is otherwise write-only: Mumble reads the mapping, nothing
+//! reads it back. This lets overlays, companion processes, or anyone
+//! debugging the positional data inspect what was actually written without
+//! re-opening the shared memory themselves.
+
+use serde::Serialize;
+
+use crate::{MumbleLink, WideChar};
+
+/// An owned, serializable snapshot of the live `MumbleLink` mapping.
+#[derive(Serialize)]
+pub struct Snapshot {
+    pub ui_tick: u32,
+    pub avatar_position: [f32; 3],
+    pub avatar_front: [f32; 3],
+    pub avatar_top: [f32; 3],
+    pub camera_position: [f32; 3],
+    pub camera_front: [f32; 3],
+    pub camera_top: [f32; 3],
+    pub name: String,
+    pub identity: String,
+    pub context: String,
+    pub description: String,
+}
+
+impl Snapshot {
+    /// Read the current contents of `link` into an owned snapshot.
+    pub fn read(link: &MumbleLink) -> Self {
+        Snapshot {
+            ui_tick: link.ui_tick,
+            avatar_position: link.avatar_position,
+            avatar_front: link.avatar_front,
+            avatar_top: link.avatar_top,
+            camera_position: link.camera_position,
+            camera_front: link.camera_front,
+            camera_top: link.camera_top,
+            name: decode_wide(&link.name),
+            identity: decode_wide(&link.identity),
+            context: decode_context(&link.context, link.context_len),
+            description: decode_wide(&link.description),
+        }
+    }
+
+    /// Serialize this snapshot to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Snapshot is always serializable")
+    }
+}
+
+/// Decode a nul-terminated wide-char buffer into a `String`.
+fn decode_wide(buf: &[WideChar]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    buf[..len]
+        .iter()
+        .filter_map(|&c| char::from_u32(c as u32))
+        .collect()
+}
+
+/// Decode the `context` buffer, which (unlike the wide-char fields) is not
+/// nul-terminated and instead relies on `context_len`.
+fn decode_context(buf: &[u8], len: u32) -> String {
+    let len = (len as usize).min(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
@@ -0,0 +1,102 @@
+//! Coordinate transform from Minecraft world space into Mumble's axes.
+//!
+//! Minecraft blocks are already metric (one block ~= one meter), but the
+//! game is right-handed with Y up, while Mumble's `LinkedMem` contract
+//! expects a left-handed, meters-based system with +X right, +Y up, +Z
+//! front, and unit-length `*_front`/`*_top` vectors. This used to be
+//! handled by an ad-hoc axis swap (emitting X, Z, Y) that neither matched
+//! that contract nor validated its input; this module replaces it with an
+//! explicit transform plus normalization so malformed input can't feed
+//! NaNs into Mumble.
+
+/// Minimum length a vector must have before it's considered meaningful;
+/// below this we fall back to a default unit axis rather than risk
+/// dividing by (near) zero.
+const MIN_LENGTH: f32 = 1e-5;
+
+/// Map a Minecraft world-space vector (right-handed, Y up) into Mumble's
+/// left-handed axes (+X right, +Y up, +Z front).
+pub fn minecraft_to_mumble(x: f32, y: f32, z: f32) -> [f32; 3] {
+    [x, y, -z]
+}
+
+fn length(v: [f32; 3]) -> f32 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+/// Normalize a vector to unit length, substituting `default` if it's too
+/// short to have a meaningful direction.
+pub fn normalize(v: [f32; 3], default: [f32; 3]) -> [f32; 3] {
+    let len = length(v);
+    if len < MIN_LENGTH {
+        return default;
+    }
+    [v[0] / len, v[1] / len, v[2] / len]
+}
+
+/// Re-orthogonalize `top` against `front` via Gram-Schmidt, so the two axes
+/// Mumble receives are never far from perpendicular even if the source data
+/// drifted. Both inputs are assumed normalized; falls back to `default` if
+/// the result collapses to (near) zero, e.g. because `top` was parallel to
+/// `front`.
+pub fn orthogonalize_top(front: [f32; 3], top: [f32; 3], default: [f32; 3]) -> [f32; 3] {
+    let dot = front[0] * top[0] + front[1] * top[1] + front[2] * top[2];
+    let adjusted = [
+        top[0] - dot * front[0],
+        top[1] - dot * front[1],
+        top[2] - dot * front[2],
+    ];
+    normalize(adjusted, default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minecraft_to_mumble_flips_z() {
+        assert_eq!(minecraft_to_mumble(1.0, 2.0, 3.0), [1.0, 2.0, -3.0]);
+    }
+
+    #[test]
+    fn normalize_unit_vector_is_unchanged() {
+        assert_eq!(normalize([1.0, 0.0, 0.0], [0.0, 0.0, 1.0]), [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn normalize_scales_to_unit_length() {
+        assert_eq!(normalize([0.0, 2.0, 0.0], [0.0, 0.0, 1.0]), [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn normalize_zero_vector_falls_back_to_default() {
+        assert_eq!(normalize([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn normalize_near_zero_vector_falls_back_to_default() {
+        assert_eq!(
+            normalize([1e-7, 0.0, 0.0], [1.0, 0.0, 0.0]),
+            [1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn orthogonalize_top_removes_front_component() {
+        let front = [1.0, 0.0, 0.0];
+        let top = [0.5, 1.0, 0.0];
+        let result = orthogonalize_top(front, top, [0.0, 1.0, 0.0]);
+        let dot = result[0] * front[0] + result[1] * front[1] + result[2] * front[2];
+        assert!(dot.abs() < 1e-6);
+    }
+
+    #[test]
+    fn orthogonalize_top_parallel_to_front_falls_back_to_default() {
+        let front = [1.0, 0.0, 0.0];
+        let top = [2.0, 0.0, 0.0];
+        assert_eq!(
+            orthogonalize_top(front, top, [0.0, 1.0, 0.0]),
+            [0.0, 1.0, 0.0]
+        );
+    }
+}